@@ -1,44 +1,136 @@
 use std::collections::HashMap;
+use std::path::PathBuf;
 use std::sync::Arc;
 
+use futures::stream::TryStreamExt;
+use uuid::Uuid;
 use mongodb::{
-    bson::{Bson, Document},
+    bson::{doc, Bson, Document},
+    error::{BulkWriteError as DriverBulkWriteError, ErrorKind, PartialBulkWriteResult},
     event::{command::CommandEventHandler, sdam::SdamEventHandler},
-    options::{ClientOptions, FindOptions, ServerAddress, Credential, ServerApi, ServerApiVersion},
-    results::CollectionSpecification,
-    sync::{Client, Cursor},
+    options::{
+        ClientOptions, Compressor, DeleteManyModel, DeleteOneModel, FindOptions, InsertOneModel,
+        ReplaceOneModel, SelectionCriteria, ServerAddress, Credential, ServerApi,
+        ServerApiVersion, Tls, TlsOptions, UpdateManyModel, UpdateOneModel,
+        WriteModel as DriverWriteModel,
+    },
+    results::{CollectionSpecification, SummaryBulkWriteResult},
+    Client, Cursor, Namespace, ServerInfo,
 };
 use tauri::command;
 
 use crate::mongodb_events::{
-    CommandInfoHandler, ServerDescription, ServerInfoHandler, DATABASE_HEARTBEAT,
-    DATABASE_TOPOLOGY, SERVER_METRIC,
+    render_prometheus_metrics, CommandInfoHandler, ServerDescription, ServerInfoHandler,
+    DATABASE_HEARTBEAT, DATABASE_TOPOLOGY, SERVER_METRIC,
 };
 use crate::{error::PError, model::DatabaseInformation};
 use crate::{
-    model::{AppArg, BsonType},
+    model::{
+        has_usable_sort_keys, range_filter, sort_key_values, AppArg, BsonType, BulkWriteError,
+        BulkWriteSummary, CompressorConfig, ConnectionConfig, DocumentPage, NodeTarget,
+        QueryRequest, ResponsePolicy, WriteModel,
+    },
     mongodb_events::FinishedCommandInfo,
+    tasks::{self, TaskSnapshot, TASK_STORE},
 };
 
+/// Translates the caller's requested compressors into the driver's
+/// `Compressor` type, silently dropping any whose feature flag isn't
+/// compiled in rather than failing the whole connection over it.
+fn build_compressors(requested: Vec<CompressorConfig>) -> Vec<Compressor> {
+    requested
+        .into_iter()
+        .filter_map(|compressor| match compressor {
+            #[cfg(feature = "zstd-compression")]
+            CompressorConfig::Zstd => Some(Compressor::Zstd { level: None }),
+            #[cfg(not(feature = "zstd-compression"))]
+            CompressorConfig::Zstd => None,
+
+            #[cfg(feature = "snappy-compression")]
+            CompressorConfig::Snappy => Some(Compressor::Snappy),
+            #[cfg(not(feature = "snappy-compression"))]
+            CompressorConfig::Snappy => None,
+
+            #[cfg(feature = "zlib-compression")]
+            CompressorConfig::Zlib { level } => Some(Compressor::Zlib { level }),
+            #[cfg(not(feature = "zlib-compression"))]
+            CompressorConfig::Zlib { .. } => None,
+        })
+        .collect()
+}
+
 #[command]
 pub async fn mongodb_connect(
     state: AppArg<'_>,
     url: String,
     port: u16,
+    connection: Option<ConnectionConfig>,
 ) -> Result<Document, PError> {
-println!("Starting: {}", url);
-    let client = Client::with_uri_str(url)?;
+    println!("Starting: {}", url);
+    let mut options = ClientOptions::parse(&url).await?;
+
+    // `ClientOptions::parse` already pulls a port out of an explicit
+    // `host:port` in the URL or an SRV lookup; `port` only fills in hosts
+    // that didn't specify one.
+    for host in &mut options.hosts {
+        if let ServerAddress::Tcp { port: host_port, .. } = host {
+            if host_port.is_none() {
+                *host_port = Some(port);
+            }
+        }
+    }
+
+    if let Some(connection) = connection {
+        if let Some(credential) = connection.credential {
+            options.credential = Some(
+                Credential::builder()
+                    .username(credential.username)
+                    .password(credential.password)
+                    .source(credential.auth_source)
+                    .build(),
+            );
+        }
+
+        if connection.pin_server_api_v1 {
+            options.server_api = Some(ServerApi::builder().version(ServerApiVersion::V1).build());
+        }
+
+        if let Some(tls) = connection.tls {
+            let mut tls_options =
+                TlsOptions::builder().allow_invalid_certificates(tls.allow_invalid_certificates);
+            if let Some(ca_file_path) = tls.ca_file_path {
+                tls_options = tls_options.ca_file_path(PathBuf::from(ca_file_path));
+            }
+            options.tls = Some(Tls::Enabled(tls_options.build()));
+        }
+
+        if !connection.compressors.is_empty() {
+            options.compressors = Some(build_compressors(connection.compressors));
+        }
+    }
+
+    options.command_event_handler = Some(Arc::new(CommandInfoHandler).into());
+    options.sdam_event_handler = Some(Arc::new(ServerInfoHandler).into());
+
+    let client = Client::with_options(options)?;
     println!("Connected successfully");
-    let result = DatabaseInformation::from_client(&client)?;
+    let result = DatabaseInformation::from_client(&client).await?;
 
     {
-        let mut handle = state.client.lock().unwrap();
+        let mut handle = state.client.write().await;
         *handle = Some(client);
     }
 
     Ok(result)
 }
 
+/// Pages through a collection. When `documents_sort` carries a usable sort
+/// key and `after` is given, this builds a `{ sortField: { $gt: after } }`
+/// style range filter (generalized to the whole sort tuple) instead of
+/// `.skip()`, so deep pages of large collections don't force the server to
+/// walk and discard everything before them. Falls back to skip-based
+/// paging when there's no usable sort key to range over, or when a page
+/// past the first is requested without an `after` cursor.
 #[command]
 pub async fn mongodb_find_documents(
     state: AppArg<'_>,
@@ -49,21 +141,325 @@ pub async fn mongodb_find_documents(
     documents_filter: Document,
     documents_projection: Document,
     documents_sort: Document,
-) -> Result<Vec<Document>, PError> {
-    let handle = &*state.client.lock().unwrap();
+    after: Option<Document>,
+) -> Result<DocumentPage, PError> {
+    let handle = state.client.read().await;
     let client = handle.as_ref().ok_or(PError::ClientNotAvailable)?;
     let database = client.database(&database_name);
     let collections = database.collection(&collection_name);
-    let find_options = FindOptions::builder()
+
+    let mut find_options = FindOptions::builder()
         .limit(per_page)
-        .skip((per_page * page) as u64)
         .projection(documents_projection)
-        .sort(documents_sort)
+        .sort(documents_sort.clone())
         .build();
-    let result = collections
-        .find(documents_filter, find_options)
-        .and_then(|cursor| cursor.collect::<Result<Vec<_>, _>>())?;
-    Ok(result)
+
+    let filter = match after.as_ref().and_then(|after| range_filter(&documents_sort, after)) {
+        Some(range) => doc! { "$and": [Bson::Document(documents_filter), Bson::Document(range)] },
+        None => {
+            // No `after` cursor yet, or no usable sort key to range over.
+            // Only the cursor-less first page can do without `skip`:
+            // ranging over an absent cursor on page 0 already means "from
+            // the start", same as `skip(0)`.
+            if has_usable_sort_keys(&documents_sort) && after.is_none() && page == 0 {
+                documents_filter
+            } else {
+                find_options.skip = Some((per_page * page) as u64);
+                documents_filter
+            }
+        }
+    };
+
+    let cursor: Cursor<Document> = collections.find(filter, find_options).await?;
+    let documents: Vec<Document> = cursor.try_collect().await?;
+    let next_after = documents
+        .last()
+        .map(|document| sort_key_values(&documents_sort, document));
+
+    Ok(DocumentPage {
+        documents,
+        next_after,
+    })
+}
+
+/// Maps our request-facing `WriteModel` onto the driver's native bulk write
+/// model, scoped to a single namespace since the Tauri command only ever
+/// targets one collection at a time.
+fn to_driver_models(namespace: &Namespace, models: Vec<WriteModel>) -> Vec<DriverWriteModel> {
+    models
+        .into_iter()
+        .map(|model| match model {
+            WriteModel::InsertOne { document } => InsertOneModel::builder()
+                .namespace(namespace.clone())
+                .document(document)
+                .build()
+                .into(),
+            WriteModel::UpdateOne {
+                filter,
+                update,
+                upsert,
+            } => UpdateOneModel::builder()
+                .namespace(namespace.clone())
+                .filter(filter)
+                .update(update)
+                .upsert(upsert)
+                .build()
+                .into(),
+            WriteModel::UpdateMany {
+                filter,
+                update,
+                upsert,
+            } => UpdateManyModel::builder()
+                .namespace(namespace.clone())
+                .filter(filter)
+                .update(update)
+                .upsert(upsert)
+                .build()
+                .into(),
+            WriteModel::ReplaceOne {
+                filter,
+                replacement,
+                upsert,
+            } => ReplaceOneModel::builder()
+                .namespace(namespace.clone())
+                .filter(filter)
+                .replacement(replacement)
+                .upsert(upsert)
+                .build()
+                .into(),
+            WriteModel::DeleteOne { filter } => DeleteOneModel::builder()
+                .namespace(namespace.clone())
+                .filter(filter)
+                .build()
+                .into(),
+            WriteModel::DeleteMany { filter } => DeleteManyModel::builder()
+                .namespace(namespace.clone())
+                .filter(filter)
+                .build()
+                .into(),
+        })
+        .collect()
+}
+
+/// Turns a completed (non-verbose) `SummaryBulkWriteResult` into our summary
+/// DTO. The driver only totals upserts in summary mode rather than tracking
+/// their individual `_id`s, so `BulkWriteSummary` mirrors that.
+fn summary_from_result(result: SummaryBulkWriteResult) -> BulkWriteSummary {
+    BulkWriteSummary {
+        inserted_count: result.inserted_count as u64,
+        matched_count: result.matched_count as u64,
+        modified_count: result.modified_count as u64,
+        deleted_count: result.deleted_count as u64,
+        upserted_count: result.upserted_count as u64,
+        write_errors: Vec::new(),
+    }
+}
+
+/// Turns a partial `BulkWriteError` (the `ordered: false` case, or an
+/// `ordered: true` batch that stopped partway through) into our summary
+/// DTO, carrying over whatever operations did succeed alongside the
+/// per-index write errors so the UI can report the partial failure.
+fn summary_from_bulk_write_error(error: &DriverBulkWriteError) -> BulkWriteSummary {
+    let mut summary = match &error.partial_result {
+        Some(PartialBulkWriteResult::Summary(result)) => summary_from_result(result.clone()),
+        // Verbose results aren't requested by `mongodb_bulk_write`, and no
+        // top-level error occurred, so there's nothing to report beyond the
+        // write errors below.
+        Some(PartialBulkWriteResult::Verbose(_)) | None => BulkWriteSummary::default(),
+    };
+    summary.write_errors = error
+        .write_errors
+        .iter()
+        .map(|(index, write_error)| BulkWriteError {
+            index: *index,
+            message: write_error.message.clone(),
+        })
+        .collect();
+    summary
+}
+
+/// Runs `models` through the driver's native bulk write command (batched
+/// `bulkWrite` calls rather than one call per model), honoring `ordered` the same way the
+/// driver's own semantics do, and returning a summary the UI can use to
+/// report partial failures.
+#[command]
+pub async fn mongodb_bulk_write(
+    state: AppArg<'_>,
+    database_name: String,
+    collection_name: String,
+    models: Vec<WriteModel>,
+    ordered: bool,
+) -> Result<BulkWriteSummary, PError> {
+    let handle = state.client.read().await;
+    let client = handle.as_ref().ok_or(PError::ClientNotAvailable)?;
+    let namespace = Namespace {
+        db: database_name,
+        coll: collection_name,
+    };
+    let driver_models = to_driver_models(&namespace, models);
+
+    match client.bulk_write(driver_models).ordered(ordered).await {
+        Ok(result) => Ok(summary_from_result(result)),
+        Err(err) => match *err.kind {
+            ErrorKind::BulkWrite(ref bulk_write_error) => {
+                Ok(summary_from_bulk_write_error(bulk_write_error))
+            }
+            _ => Err(err.into()),
+        },
+    }
+}
+
+#[cfg(test)]
+mod bulk_write_tests {
+    use super::*;
+
+    fn namespace() -> Namespace {
+        Namespace {
+            db: "test".to_string(),
+            coll: "widgets".to_string(),
+        }
+    }
+
+    #[test]
+    fn maps_write_models_in_order_keeping_namespace_and_upsert() {
+        let models = vec![
+            WriteModel::InsertOne {
+                document: doc! { "a": 1 },
+            },
+            WriteModel::UpdateOne {
+                filter: doc! { "a": 1 },
+                update: doc! { "$set": { "a": 2 } },
+                upsert: true,
+            },
+            WriteModel::DeleteMany {
+                filter: doc! { "a": 2 },
+            },
+        ];
+
+        let driver_models = to_driver_models(&namespace(), models);
+
+        assert_eq!(driver_models.len(), 3);
+        assert!(matches!(
+            &driver_models[0],
+            DriverWriteModel::InsertOne(model) if model.namespace == namespace()
+        ));
+        assert!(matches!(
+            &driver_models[1],
+            DriverWriteModel::UpdateOne(model) if model.upsert == Some(true)
+        ));
+        assert!(matches!(
+            &driver_models[2],
+            DriverWriteModel::DeleteMany(model) if model.namespace == namespace()
+        ));
+    }
+
+    #[test]
+    fn partial_bulk_write_error_surfaces_successes_and_write_errors() {
+        let mut partial_result = SummaryBulkWriteResult::default();
+        partial_result.inserted_count = 2;
+
+        let write_error: mongodb::error::WriteError = mongodb::bson::from_document(doc! {
+            "code": 11000,
+            "errmsg": "duplicate key",
+        })
+        .unwrap();
+
+        let mut bulk_write_error = DriverBulkWriteError::default();
+        bulk_write_error.write_errors.insert(2, write_error);
+        bulk_write_error.partial_result =
+            Some(PartialBulkWriteResult::Summary(partial_result));
+
+        let summary = summary_from_bulk_write_error(&bulk_write_error);
+
+        assert_eq!(summary.inserted_count, 2);
+        assert_eq!(summary.write_errors.len(), 1);
+        assert_eq!(summary.write_errors[0].index, 2);
+        assert_eq!(summary.write_errors[0].message, "duplicate key");
+    }
+}
+
+#[cfg(test)]
+mod run_on_nodes_tests {
+    use super::*;
+    use std::io;
+
+    fn node_error(message: &str) -> mongodb::error::Error {
+        mongodb::error::Error::custom(io::Error::new(io::ErrorKind::Other, message.to_string()))
+    }
+
+    #[test]
+    fn sum_policy_adds_n_across_nodes() {
+        let responses = vec![
+            ("a".to_string(), Ok(doc! { "n": 2 })),
+            ("b".to_string(), Ok(doc! { "n": 3 })),
+        ];
+        let merged = merge_node_responses(ResponsePolicy::Sum, responses).unwrap();
+        assert_eq!(merged, Bson::Int64(5));
+    }
+
+    #[test]
+    fn sum_policy_propagates_the_first_node_error() {
+        let responses = vec![
+            ("a".to_string(), Ok(doc! { "n": 2 })),
+            ("b".to_string(), Err(node_error("boom"))),
+        ];
+        assert!(merge_node_responses(ResponsePolicy::Sum, responses).is_err());
+    }
+
+    #[test]
+    fn concat_policy_collects_every_response_in_order() {
+        let responses = vec![
+            ("a".to_string(), Ok(doc! { "ok": 1 })),
+            ("b".to_string(), Ok(doc! { "ok": 2 })),
+        ];
+        let merged = merge_node_responses(ResponsePolicy::Concat, responses).unwrap();
+        assert_eq!(
+            merged,
+            Bson::Array(vec![
+                Bson::Document(doc! { "ok": 1 }),
+                Bson::Document(doc! { "ok": 2 }),
+            ])
+        );
+    }
+
+    #[test]
+    fn first_success_policy_skips_failed_nodes() {
+        let responses = vec![
+            ("a".to_string(), Err(node_error("unreachable"))),
+            ("b".to_string(), Ok(doc! { "ok": 1 })),
+        ];
+        let merged = merge_node_responses(ResponsePolicy::FirstSuccess, responses).unwrap();
+        assert_eq!(merged, Bson::Document(doc! { "ok": 1 }));
+    }
+
+    #[test]
+    fn first_success_policy_errors_when_every_node_fails() {
+        let responses = vec![("a".to_string(), Err(node_error("unreachable")))];
+        let result = merge_node_responses(ResponsePolicy::FirstSuccess, responses);
+        assert!(matches!(result, Err(PError::NoMatchingServer)));
+    }
+
+    #[test]
+    fn all_policy_keys_each_response_by_address_and_keeps_errors_as_strings() {
+        let responses = vec![
+            ("a".to_string(), Ok(doc! { "ok": 1 })),
+            ("b".to_string(), Err(node_error("unreachable"))),
+        ];
+        let merged = merge_node_responses(ResponsePolicy::All, responses).unwrap();
+        let Bson::Document(by_address) = merged else {
+            panic!("expected a document");
+        };
+        assert_eq!(by_address.get("a"), Some(&Bson::Document(doc! { "ok": 1 })));
+        assert!(matches!(by_address.get("b"), Some(Bson::String(_))));
+    }
+
+    #[test]
+    fn resolve_target_addresses_for_a_single_member_ignores_the_topology() {
+        let addresses = resolve_target_addresses(&NodeTarget::Member {
+            address: "node-a:27017".to_string(),
+        });
+        assert_eq!(addresses, vec!["node-a:27017".to_string()]);
+    }
 }
 
 #[command]
@@ -73,11 +469,11 @@ pub async fn mongodb_count_documents(
     collection_name: String,
     documents_filter: Document,
 ) -> Result<u64, PError> {
-    let handle = &*state.client.lock().unwrap();
+    let handle = state.client.read().await;
     let client = handle.as_ref().ok_or(PError::ClientNotAvailable)?;
     let database = client.database(&database_name);
     let collections = database.collection::<Document>(&collection_name);
-    let result = collections.count_documents(documents_filter, None)?;
+    let result = collections.count_documents(documents_filter, None).await?;
     Ok(result)
 }
 
@@ -88,16 +484,123 @@ pub async fn mongodb_aggregate_documents(
     collection_name: String,
     stages: Vec<Document>,
 ) -> Result<Vec<Document>, PError> {
-    let handle = &*state.client.lock().unwrap();
+    let handle = state.client.read().await;
     let client = handle.as_ref().ok_or(PError::ClientNotAvailable)?;
     let database = client.database(&database_name);
     let collections = database.collection::<Document>(&collection_name);
-    let result = collections
-        .aggregate(stages, None)
-        .and_then(|cursor| cursor.collect::<Result<Vec<Document>, _>>())?;
+    let cursor = collections.aggregate(stages, None).await?;
+    let result = cursor.try_collect().await?;
     Ok(result)
 }
 
+/// Resolves a `NodeTarget` against the last known topology into the set of
+/// server addresses a `mongodb_run_on_nodes` call should fan out to.
+fn resolve_target_addresses(target: &NodeTarget) -> Vec<String> {
+    match target {
+        NodeTarget::Member { address } => vec![address.clone()],
+        NodeTarget::AllPrimaries => DATABASE_TOPOLOGY
+            .lock()
+            .unwrap()
+            .get_database_topology()
+            .into_iter()
+            .filter(|server| matches!(server.server_type.as_str(), "RsPrimary" | "Standalone"))
+            .map(|server| server.address)
+            .collect(),
+        NodeTarget::AllMembers => DATABASE_TOPOLOGY
+            .lock()
+            .unwrap()
+            .get_database_topology()
+            .into_iter()
+            .map(|server| server.address)
+            .collect(),
+    }
+}
+
+/// Merges the per-node responses of `mongodb_run_on_nodes` according to the
+/// caller's response policy, mirroring the `sum`/`concat`/`first-success`/
+/// `all` policies a cluster client would use to fan a command out across
+/// every member and reconcile the replies.
+fn merge_node_responses(
+    policy: ResponsePolicy,
+    responses: Vec<(String, Result<Document, mongodb::error::Error>)>,
+) -> Result<Bson, PError> {
+    match policy {
+        ResponsePolicy::Sum => {
+            let mut total: i64 = 0;
+            for (_, response) in responses {
+                let document = response?;
+                total += document
+                    .get_i64("n")
+                    .or_else(|_| document.get_i32("n").map(i64::from))
+                    .unwrap_or(0);
+            }
+            Ok(Bson::Int64(total))
+        }
+        ResponsePolicy::Concat => {
+            let mut documents = Vec::with_capacity(responses.len());
+            for (_, response) in responses {
+                documents.push(Bson::Document(response?));
+            }
+            Ok(Bson::Array(documents))
+        }
+        ResponsePolicy::FirstSuccess => responses
+            .into_iter()
+            .find_map(|(_, response)| response.ok())
+            .map(Bson::Document)
+            .ok_or(PError::NoMatchingServer),
+        ResponsePolicy::All => {
+            let mut by_address = Document::new();
+            for (address, response) in responses {
+                let value = match response {
+                    Ok(document) => Bson::Document(document),
+                    Err(err) => Bson::String(err.to_string()),
+                };
+                by_address.insert(address, value);
+            }
+            Ok(Bson::Document(by_address))
+        }
+    }
+}
+
+/// Runs a raw command against a chosen replica-set member, every primary,
+/// or every member, dispatching to each selected node concurrently and
+/// merging the results according to `policy`. Lets the UI compare, for
+/// example, `serverStatus` across every node of a sharded/replica
+/// deployment in one call.
+#[command]
+pub async fn mongodb_run_on_nodes(
+    state: AppArg<'_>,
+    database_name: String,
+    target: NodeTarget,
+    policy: ResponsePolicy,
+    command: Document,
+) -> Result<Bson, PError> {
+    let handle = state.client.read().await;
+    let client = handle.as_ref().ok_or(PError::ClientNotAvailable)?;
+    let database = client.database(&database_name);
+
+    let addresses = resolve_target_addresses(&target);
+    if addresses.is_empty() {
+        return Err(PError::NoMatchingServer);
+    }
+
+    let futures = addresses.into_iter().map(|address| {
+        let database = database.clone();
+        let command = command.clone();
+        async move {
+            let predicate_address = address.clone();
+            let criteria = SelectionCriteria::Predicate(Arc::new(move |server: &ServerInfo| {
+                server.address().to_string() == predicate_address
+            }));
+            let response = database.run_command(command, criteria).await;
+            (address, response)
+        }
+    });
+    let responses = futures::future::join_all(futures).await;
+
+    merge_node_responses(policy, responses)
+}
+
 #[command]
 pub async fn mongodb_get_database_topology() -> Vec<ServerDescription> {
     let handle = &*DATABASE_TOPOLOGY.lock().unwrap();
@@ -122,6 +625,42 @@ pub async fn mongodb_n_slowest_commands(count: usize) -> Vec<FinishedCommandInfo
     handle.get_n_slowest_commands(count)
 }
 
+/// Renders collected command rates/latencies, heartbeats, and topology
+/// state as a Prometheus/OpenMetrics exposition-format string so the app
+/// can be scraped instead of read one chart at a time.
+#[command]
+pub async fn mongodb_export_metrics() -> String {
+    render_prometheus_metrics()
+}
+
+/// Enqueues a `Find`/`Aggregate`/`Analyze` style query on a background
+/// worker and returns its task id immediately, instead of blocking the
+/// invoking command until the full cursor is drained.
+#[command]
+pub async fn mongodb_submit_query(
+    state: AppArg<'_>,
+    request: QueryRequest,
+) -> Result<Uuid, PError> {
+    let handle = state.client.read().await;
+    let client = handle.as_ref().ok_or(PError::ClientNotAvailable)?.clone();
+    Ok(tasks::submit(client, request))
+}
+
+#[command]
+pub async fn mongodb_get_task(task_id: Uuid) -> Option<TaskSnapshot> {
+    TASK_STORE.lock().unwrap().get(task_id)
+}
+
+#[command]
+pub async fn mongodb_list_tasks() -> Vec<TaskSnapshot> {
+    TASK_STORE.lock().unwrap().list()
+}
+
+#[command]
+pub async fn mongodb_cancel_task(task_id: Uuid) -> bool {
+    TASK_STORE.lock().unwrap().cancel(task_id)
+}
+
 #[command]
 pub async fn mongodb_analyze_documents(
     state: AppArg<'_>,
@@ -129,16 +668,15 @@ pub async fn mongodb_analyze_documents(
     collection_name: String,
     documents_filter: Document,
 ) -> Result<Vec<(String, Vec<(BsonType, u64)>)>, PError> {
-    let handle = &*state.client.lock().unwrap();
+    let handle = state.client.read().await;
     let client = handle.as_ref().ok_or(PError::ClientNotAvailable)?;
     let database = client.database(&database_name);
     let collections = database.collection(&collection_name);
     let find_options = FindOptions::builder().limit(1000).build();
 
-    let cursor: Cursor<Document> = collections.find(documents_filter, find_options)?;
+    let mut cursor: Cursor<Document> = collections.find(documents_filter, find_options).await?;
     let mut result: HashMap<String, HashMap<BsonType, u64>> = HashMap::default();
-    for document_cursor in cursor {
-        let document = document_cursor?;
+    while let Some(document) = cursor.try_next().await? {
         for (document_key, document_value) in &document {
             let document_value_bson_type = BsonType::from(document_value);
             let entry: &mut HashMap<BsonType, u64> =
@@ -0,0 +1,35 @@
+use serde::Serialize;
+
+/// Error type returned to the frontend by every `#[command]` in `cmd`.
+#[derive(Debug, thiserror::Error, Serialize)]
+pub enum PError {
+    #[error("no active MongoDB client; call mongodb_connect first")]
+    ClientNotAvailable,
+
+    #[error("no server in the current topology matched the requested target or answered successfully")]
+    NoMatchingServer,
+
+    #[error("mongodb error: {0}")]
+    Mongo(String),
+
+    #[error("bson error: {0}")]
+    Bson(String),
+}
+
+impl From<mongodb::error::Error> for PError {
+    fn from(err: mongodb::error::Error) -> Self {
+        PError::Mongo(err.to_string())
+    }
+}
+
+impl From<mongodb::bson::ser::Error> for PError {
+    fn from(err: mongodb::bson::ser::Error) -> Self {
+        PError::Bson(err.to_string())
+    }
+}
+
+impl From<mongodb::bson::document::ValueAccessError> for PError {
+    fn from(err: mongodb::bson::document::ValueAccessError) -> Self {
+        PError::Bson(err.to_string())
+    }
+}
@@ -5,6 +5,7 @@ mod cmd;
 mod error;
 mod model;
 mod mongodb_events;
+mod tasks;
 
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
 pub fn run() {
@@ -14,14 +15,21 @@ pub fn run() {
         .manage(model::AppState::default())
         .invoke_handler(tauri::generate_handler![
             cmd::mongodb_connect,
+            cmd::mongodb_bulk_write,
             cmd::mongodb_find_documents,
             cmd::mongodb_count_documents,
             cmd::mongodb_aggregate_documents,
+            cmd::mongodb_run_on_nodes,
             cmd::mongodb_get_database_topology,
             cmd::mongodb_analyze_documents,
             cmd::mongodb_n_slowest_commands,
+            cmd::mongodb_export_metrics,
             cmd::mongodb_get_commands_statistics_per_sec,
-            cmd::mongodb_get_connection_heartbeat
+            cmd::mongodb_get_connection_heartbeat,
+            cmd::mongodb_submit_query,
+            cmd::mongodb_get_task,
+            cmd::mongodb_list_tasks,
+            cmd::mongodb_cancel_task
         ])
         .run(tauri::generate_context!())
         .expect("error while running tauri application");
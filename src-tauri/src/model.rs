@@ -0,0 +1,368 @@
+use mongodb::bson::{doc, Bson, Document};
+use mongodb::Client;
+use serde::{Deserialize, Serialize};
+use tauri::State;
+use tokio::sync::RwLock;
+
+use crate::error::PError;
+
+/// Shared Tauri state: the single MongoDB client the UI is currently
+/// connected to, if any. `Client` is a thin, cheaply-cloneable handle onto
+/// the driver's connection pool, so holding it behind an `RwLock` lets
+/// concurrent reads (finds, aggregations, topology polling) proceed without
+/// blocking each other; only `mongodb_connect` ever takes the write half.
+#[derive(Default)]
+pub struct AppState {
+    pub client: RwLock<Option<Client>>,
+}
+
+pub type AppArg<'a> = State<'a, AppState>;
+
+/// Build/server info surfaced to the UI right after `mongodb_connect`.
+pub struct DatabaseInformation;
+
+impl DatabaseInformation {
+    pub async fn from_client(client: &Client) -> Result<Document, PError> {
+        let mut build_info = client
+            .database("admin")
+            .run_command(doc! { "buildInfo": 1 }, None)
+            .await?;
+        // `hello`'s `compression` array reflects the compressors both sides
+        // agreed on during the handshake, so surface it alongside build info
+        // rather than just echoing back what the caller requested.
+        if let Ok(hello) = client
+            .database("admin")
+            .run_command(doc! { "hello": 1 }, None)
+            .await
+        {
+            if let Ok(negotiated) = hello.get_array("compression") {
+                build_info.insert("negotiatedCompressors", negotiated.clone());
+            }
+        }
+        Ok(build_info)
+    }
+}
+
+/// Username/password credential for `mongodb_connect`, applied against the
+/// given `auth_source` database (defaults to `admin`, matching the driver).
+#[derive(Debug, Clone, Deserialize)]
+pub struct ConnectionCredential {
+    pub username: String,
+    pub password: String,
+    #[serde(default = "default_auth_source")]
+    pub auth_source: String,
+}
+
+fn default_auth_source() -> String {
+    "admin".to_string()
+}
+
+/// TLS settings for `mongodb_connect`.
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct TlsConfig {
+    #[serde(default)]
+    pub ca_file_path: Option<String>,
+    #[serde(default)]
+    pub allow_invalid_certificates: bool,
+}
+
+/// A single requested wire-protocol compressor, gated behind the matching
+/// `mongodb` driver feature flag at build time.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(tag = "algorithm", rename_all = "lowercase")]
+pub enum CompressorConfig {
+    Zstd,
+    Snappy,
+    Zlib {
+        #[serde(default)]
+        level: Option<i32>,
+    },
+}
+
+/// Structured connection options for `mongodb_connect`, covering everything
+/// `Client::with_uri_str` left on the table: auth, a pinned stable API
+/// version, TLS, and wire compression.
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct ConnectionConfig {
+    #[serde(default)]
+    pub credential: Option<ConnectionCredential>,
+    #[serde(default)]
+    pub pin_server_api_v1: bool,
+    #[serde(default)]
+    pub tls: Option<TlsConfig>,
+    #[serde(default)]
+    pub compressors: Vec<CompressorConfig>,
+}
+
+/// Coarse BSON type tag used to summarize a field's value distribution in
+/// `mongodb_analyze_documents`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize)]
+pub enum BsonType {
+    Double,
+    String,
+    Document,
+    Array,
+    Boolean,
+    Null,
+    ObjectId,
+    DateTime,
+    Int32,
+    Int64,
+    Other,
+}
+
+impl From<&Bson> for BsonType {
+    fn from(value: &Bson) -> Self {
+        match value {
+            Bson::Double(_) => BsonType::Double,
+            Bson::String(_) => BsonType::String,
+            Bson::Document(_) => BsonType::Document,
+            Bson::Array(_) => BsonType::Array,
+            Bson::Boolean(_) => BsonType::Boolean,
+            Bson::Null => BsonType::Null,
+            Bson::ObjectId(_) => BsonType::ObjectId,
+            Bson::DateTime(_) => BsonType::DateTime,
+            Bson::Int32(_) => BsonType::Int32,
+            Bson::Int64(_) => BsonType::Int64,
+            _ => BsonType::Other,
+        }
+    }
+}
+
+/// One operation in a `mongodb_bulk_write` batch, mirroring the driver's
+/// write models.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(tag = "type")]
+pub enum WriteModel {
+    InsertOne {
+        document: Document,
+    },
+    UpdateOne {
+        filter: Document,
+        update: Document,
+        #[serde(default)]
+        upsert: bool,
+    },
+    UpdateMany {
+        filter: Document,
+        update: Document,
+        #[serde(default)]
+        upsert: bool,
+    },
+    ReplaceOne {
+        filter: Document,
+        replacement: Document,
+        #[serde(default)]
+        upsert: bool,
+    },
+    DeleteOne {
+        filter: Document,
+    },
+    DeleteMany {
+        filter: Document,
+    },
+}
+
+/// The write error for a single failed index within a bulk write batch.
+#[derive(Debug, Serialize)]
+pub struct BulkWriteError {
+    pub index: usize,
+    pub message: String,
+}
+
+/// Aggregate result of a `mongodb_bulk_write` call, letting the UI report
+/// partial failures for `ordered: false` batches. Mirrors the driver's
+/// summary (non-verbose) bulk write result, which only totals upserts
+/// rather than tracking their individual `_id`s.
+#[derive(Debug, Default, Serialize)]
+pub struct BulkWriteSummary {
+    pub inserted_count: u64,
+    pub matched_count: u64,
+    pub modified_count: u64,
+    pub deleted_count: u64,
+    pub upserted_count: u64,
+    pub write_errors: Vec<BulkWriteError>,
+}
+
+/// Which node(s) a `mongodb_run_on_nodes` command should be dispatched to.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(tag = "kind", rename_all = "kebab-case")]
+pub enum NodeTarget {
+    Member { address: String },
+    AllPrimaries,
+    AllMembers,
+}
+
+/// How the per-node responses of a `mongodb_run_on_nodes` command should be
+/// merged back into a single result.
+#[derive(Debug, Clone, Copy, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum ResponsePolicy {
+    Sum,
+    Concat,
+    FirstSuccess,
+    All,
+}
+
+/// A page of `mongodb_find_documents` results. `next_after` is the sort-key
+/// tuple of the last returned document, to be passed back in as `after` to
+/// fetch the next page without a `skip`-induced server-side scan.
+#[derive(Debug, Serialize)]
+pub struct DocumentPage {
+    pub documents: Vec<Document>,
+    pub next_after: Option<Document>,
+}
+
+/// Pulls `(field, direction)` pairs out of a `documents_sort` spec, in
+/// declaration order, ignoring anything that isn't a `1`/`-1` direction.
+fn sort_keys(sort: &Document) -> Vec<(String, i64)> {
+    sort.iter()
+        .filter_map(|(field, direction)| {
+            direction
+                .as_i32()
+                .map(i64::from)
+                .or_else(|| direction.as_i64())
+                .filter(|direction| *direction == 1 || *direction == -1)
+                .map(|direction| (field.clone(), direction))
+        })
+        .collect()
+}
+
+/// Whether `sort` has at least one field `range_filter` can range over.
+pub fn has_usable_sort_keys(sort: &Document) -> bool {
+    !sort_keys(sort).is_empty()
+}
+
+/// Builds the compound range predicate `{ $or: [...] }` that selects
+/// documents strictly after `after` in the order defined by `sort`,
+/// equivalent to `{ sortField: { $gt: after } }` generalized to a tuple of
+/// sort fields. Returns `None` if `sort` has no usable keys or `after` is
+/// missing one of them, so the caller can fall back to skip-based paging.
+pub fn range_filter(sort: &Document, after: &Document) -> Option<Document> {
+    let keys = sort_keys(sort);
+    if keys.is_empty() {
+        return None;
+    }
+
+    let mut clauses = Vec::with_capacity(keys.len());
+    for (index, (field, direction)) in keys.iter().enumerate() {
+        let after_value = after.get(field)?;
+        let operator = if *direction >= 0 { "$gt" } else { "$lt" };
+
+        let mut clause = Document::new();
+        for (prior_field, _) in &keys[..index] {
+            clause.insert(prior_field, after.get(prior_field)?.clone());
+        }
+        clause.insert(field, doc! { operator: after_value.clone() });
+        clauses.push(Bson::Document(clause));
+    }
+
+    Some(doc! { "$or": clauses })
+}
+
+/// Projects a document down to just its sort-key fields, for use as the
+/// `next_after` token of the page it came from.
+pub fn sort_key_values(sort: &Document, document: &Document) -> Document {
+    let mut after = Document::new();
+    for (field, _) in sort_keys(sort) {
+        if let Some(value) = document.get(&field) {
+            after.insert(field, value.clone());
+        }
+    }
+    after
+}
+
+/// A query to hand off to the background task worker via
+/// `mongodb_submit_query`, covering the same heavy, cursor-draining
+/// operations the synchronous commands already expose.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(tag = "operation")]
+pub enum QueryRequest {
+    Find {
+        database_name: String,
+        collection_name: String,
+        documents_filter: Document,
+        documents_projection: Document,
+        documents_sort: Document,
+    },
+    Aggregate {
+        database_name: String,
+        collection_name: String,
+        stages: Vec<Document>,
+    },
+    Analyze {
+        database_name: String,
+        collection_name: String,
+        documents_filter: Document,
+    },
+}
+
+#[cfg(test)]
+mod range_pagination_tests {
+    use super::*;
+
+    #[test]
+    fn has_usable_sort_keys_ignores_non_numeric_directions() {
+        assert!(!has_usable_sort_keys(&doc! { "name": "text" }));
+        assert!(has_usable_sort_keys(&doc! { "age": 1 }));
+    }
+
+    #[test]
+    fn range_filter_single_ascending_key_uses_gt() {
+        let sort = doc! { "age": 1 };
+        let after = doc! { "age": 30 };
+        let filter = range_filter(&sort, &after).unwrap();
+        assert_eq!(filter, doc! { "$or": [ doc! { "age": { "$gt": 30 } } ] });
+    }
+
+    #[test]
+    fn range_filter_single_descending_key_uses_lt() {
+        let sort = doc! { "age": -1 };
+        let after = doc! { "age": 30 };
+        let filter = range_filter(&sort, &after).unwrap();
+        assert_eq!(filter, doc! { "$or": [ doc! { "age": { "$lt": 30 } } ] });
+    }
+
+    #[test]
+    fn range_filter_compound_keys_builds_tiebreak_clauses() {
+        let sort = doc! { "age": 1, "_id": 1 };
+        let after = doc! { "age": 30, "_id": "abc" };
+        let filter = range_filter(&sort, &after).unwrap();
+        assert_eq!(
+            filter,
+            doc! { "$or": [
+                doc! { "age": { "$gt": 30 } },
+                doc! { "age": 30, "_id": { "$gt": "abc" } },
+            ] }
+        );
+    }
+
+    #[test]
+    fn range_filter_none_when_sort_has_no_usable_keys() {
+        assert!(range_filter(&doc! {}, &doc! { "age": 30 }).is_none());
+    }
+
+    #[test]
+    fn range_filter_none_when_after_missing_a_sort_key() {
+        let sort = doc! { "age": 1, "_id": 1 };
+        let after = doc! { "age": 30 };
+        assert!(range_filter(&sort, &after).is_none());
+    }
+
+    #[test]
+    fn sort_key_values_projects_only_sort_fields() {
+        let sort = doc! { "age": 1, "_id": 1 };
+        let document = doc! { "age": 30, "_id": "abc", "name": "irrelevant" };
+        assert_eq!(
+            sort_key_values(&sort, &document),
+            doc! { "age": 30, "_id": "abc" }
+        );
+    }
+
+    #[test]
+    fn sort_key_values_omits_fields_missing_from_document() {
+        let sort = doc! { "age": 1, "_id": 1 };
+        let document = doc! { "age": 30 };
+        assert_eq!(sort_key_values(&sort, &document), doc! { "age": 30 });
+    }
+}
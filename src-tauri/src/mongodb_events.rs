@@ -0,0 +1,329 @@
+use std::collections::{HashMap, VecDeque};
+use std::fmt::Write as _;
+use std::sync::Mutex;
+use std::time::Duration;
+
+use mongodb::event::command::{CommandEventHandler, CommandFailedEvent, CommandSucceededEvent};
+use mongodb::event::sdam::{
+    SdamEventHandler, ServerDescriptionChangedEvent, ServerHeartbeatSucceededEvent,
+    TopologyDescriptionChangedEvent,
+};
+use serde::Serialize;
+
+const RING_BUFFER_CAPACITY: usize = 512;
+
+/// Fixed histogram buckets (upper bounds, in milliseconds) for
+/// `mongodb_command_duration_milliseconds`, keeping the exported series keyed
+/// only by `command` instead of a per-sample label that would churn on
+/// every scrape.
+const DURATION_BUCKETS_MILLIS: [u64; 9] = [1, 5, 10, 25, 50, 100, 250, 500, 1000];
+
+/// A single finished command (success or failure), as seen by `CommandInfoHandler`.
+#[derive(Debug, Clone, Serialize)]
+pub struct FinishedCommandInfo {
+    pub command_name: String,
+    pub duration: Duration,
+    pub succeeded: bool,
+}
+
+/// Point-in-time view of one replica-set/sharded-cluster member.
+#[derive(Debug, Clone, Serialize)]
+pub struct ServerDescription {
+    pub address: String,
+    pub server_type: String,
+}
+
+/// Rolling log of finished commands, used for latency/rate reporting.
+#[derive(Default)]
+pub struct ServerMetric {
+    commands: VecDeque<FinishedCommandInfo>,
+}
+
+impl ServerMetric {
+    fn record(&mut self, info: FinishedCommandInfo) {
+        if self.commands.len() == RING_BUFFER_CAPACITY {
+            self.commands.pop_front();
+        }
+        self.commands.push_back(info);
+    }
+
+    pub fn get_commands_statistics_per_sec(&self, count: usize) -> Vec<(usize, usize, usize)> {
+        self.commands
+            .iter()
+            .rev()
+            .take(count)
+            .enumerate()
+            .map(|(i, info)| (i, info.duration.as_millis() as usize, info.succeeded as usize))
+            .collect()
+    }
+
+    pub fn get_n_slowest_commands(&self, count: usize) -> Vec<FinishedCommandInfo> {
+        let mut commands: Vec<FinishedCommandInfo> = self.commands.iter().cloned().collect();
+        commands.sort_by(|a, b| b.duration.cmp(&a.duration));
+        commands.truncate(count);
+        commands
+    }
+
+    fn render_prometheus(&self, out: &mut String) {
+        let mut totals: HashMap<(&str, bool), u64> = HashMap::new();
+        for info in &self.commands {
+            *totals
+                .entry((info.command_name.as_str(), info.succeeded))
+                .or_default() += 1;
+        }
+
+        let _ = writeln!(out, "# HELP mongodb_commands_total Commands observed by the driver's command monitor.");
+        let _ = writeln!(out, "# TYPE mongodb_commands_total counter");
+        for ((command_name, succeeded), count) in &totals {
+            let _ = writeln!(
+                out,
+                "mongodb_commands_total{{command=\"{command_name}\",succeeded=\"{succeeded}\"}} {count}"
+            );
+        }
+
+        let _ = writeln!(out, "# HELP mongodb_command_duration_milliseconds Duration of commands observed by the driver's command monitor.");
+        let _ = writeln!(out, "# TYPE mongodb_command_duration_milliseconds histogram");
+        let mut sums: HashMap<&str, u64> = HashMap::new();
+        let mut counts: HashMap<&str, u64> = HashMap::new();
+        let mut bucket_counts: HashMap<(&str, usize), u64> = HashMap::new();
+        for info in &self.commands {
+            let command_name = info.command_name.as_str();
+            let millis = info.duration.as_millis() as u64;
+            *sums.entry(command_name).or_default() += millis;
+            *counts.entry(command_name).or_default() += 1;
+            for (bucket_index, bound) in DURATION_BUCKETS_MILLIS.iter().enumerate() {
+                if millis <= *bound {
+                    *bucket_counts.entry((command_name, bucket_index)).or_default() += 1;
+                }
+            }
+        }
+        for command_name in counts.keys() {
+            for (bucket_index, bound) in DURATION_BUCKETS_MILLIS.iter().enumerate() {
+                // `bucket_counts[idx]` is already cumulative: the per-sample
+                // loop above incremented every bucket whose bound was >= the
+                // sample's duration, not just the smallest matching one.
+                let cumulative = bucket_counts
+                    .get(&(*command_name, bucket_index))
+                    .copied()
+                    .unwrap_or_default();
+                let _ = writeln!(
+                    out,
+                    "mongodb_command_duration_milliseconds_bucket{{command=\"{command_name}\",le=\"{bound}\"}} {cumulative}"
+                );
+            }
+            let count = counts[command_name];
+            let _ = writeln!(
+                out,
+                "mongodb_command_duration_milliseconds_bucket{{command=\"{command_name}\",le=\"+Inf\"}} {count}"
+            );
+            let _ = writeln!(
+                out,
+                "mongodb_command_duration_milliseconds_sum{{command=\"{command_name}\"}} {}",
+                sums[command_name]
+            );
+            let _ = writeln!(
+                out,
+                "mongodb_command_duration_milliseconds_count{{command=\"{command_name}\"}} {count}"
+            );
+        }
+    }
+}
+
+/// Round-trip times observed on the monitoring connection's heartbeats.
+#[derive(Default)]
+pub struct DatabaseHeartbeat {
+    round_trips: VecDeque<(usize, usize)>,
+}
+
+impl DatabaseHeartbeat {
+    pub fn get_connection_heartbeat(&self) -> Vec<(usize, usize)> {
+        self.round_trips.iter().copied().collect()
+    }
+
+    fn render_prometheus(&self, out: &mut String) {
+        let _ = writeln!(out, "# HELP mongodb_heartbeat_round_trip_milliseconds Monitoring connection heartbeat round-trip time.");
+        let _ = writeln!(out, "# TYPE mongodb_heartbeat_round_trip_milliseconds gauge");
+        for (sample, millis) in &self.round_trips {
+            let _ = writeln!(
+                out,
+                "mongodb_heartbeat_round_trip_milliseconds{{sample=\"{sample}\"}} {millis}"
+            );
+        }
+    }
+}
+
+/// Latest known topology, as reported by SDAM events.
+#[derive(Default)]
+pub struct DatabaseTopology {
+    servers: Vec<ServerDescription>,
+}
+
+impl DatabaseTopology {
+    pub fn get_database_topology(&self) -> Vec<ServerDescription> {
+        self.servers.clone()
+    }
+
+    fn render_prometheus(&self, out: &mut String) {
+        let _ = writeln!(out, "# HELP mongodb_server_up Whether the server at this address is reachable (1) or not (0), per the latest SDAM topology update.");
+        let _ = writeln!(out, "# TYPE mongodb_server_up gauge");
+        for server in &self.servers {
+            let up = if server.server_type == "Unknown" { 0 } else { 1 };
+            let _ = writeln!(
+                out,
+                "mongodb_server_up{{address=\"{}\",type=\"{}\"}} {up}",
+                server.address, server.server_type
+            );
+        }
+    }
+}
+
+/// Renders everything `SERVER_METRIC`, `DATABASE_HEARTBEAT`, and
+/// `DATABASE_TOPOLOGY` have collected so far as a single Prometheus /
+/// OpenMetrics exposition-format string, so it can be scraped or piped into
+/// an existing Grafana dashboard.
+pub fn render_prometheus_metrics() -> String {
+    let mut out = String::new();
+    SERVER_METRIC.lock().unwrap().render_prometheus(&mut out);
+    DATABASE_HEARTBEAT.lock().unwrap().render_prometheus(&mut out);
+    DATABASE_TOPOLOGY.lock().unwrap().render_prometheus(&mut out);
+    out
+}
+
+lazy_static! {
+    pub static ref SERVER_METRIC: Mutex<ServerMetric> = Mutex::new(ServerMetric::default());
+    pub static ref DATABASE_HEARTBEAT: Mutex<DatabaseHeartbeat> =
+        Mutex::new(DatabaseHeartbeat::default());
+    pub static ref DATABASE_TOPOLOGY: Mutex<DatabaseTopology> =
+        Mutex::new(DatabaseTopology::default());
+}
+
+/// Feeds `SERVER_METRIC` from the driver's command-monitoring events.
+pub struct CommandInfoHandler;
+
+impl CommandEventHandler for CommandInfoHandler {
+    fn handle_command_succeeded_event(&self, event: CommandSucceededEvent) {
+        let mut handle = SERVER_METRIC.lock().unwrap();
+        handle.record(FinishedCommandInfo {
+            command_name: event.command_name,
+            duration: event.duration,
+            succeeded: true,
+        });
+    }
+
+    fn handle_command_failed_event(&self, event: CommandFailedEvent) {
+        let mut handle = SERVER_METRIC.lock().unwrap();
+        handle.record(FinishedCommandInfo {
+            command_name: event.command_name,
+            duration: event.duration,
+            succeeded: false,
+        });
+    }
+}
+
+/// Feeds `DATABASE_HEARTBEAT` and `DATABASE_TOPOLOGY` from SDAM events.
+pub struct ServerInfoHandler;
+
+impl SdamEventHandler for ServerInfoHandler {
+    fn handle_server_heartbeat_succeeded_event(&self, event: ServerHeartbeatSucceededEvent) {
+        let mut handle = DATABASE_HEARTBEAT.lock().unwrap();
+        let index = handle.round_trips.len();
+        handle
+            .round_trips
+            .push_back((index, event.duration.as_millis() as usize));
+    }
+
+    fn handle_server_description_changed_event(&self, _event: ServerDescriptionChangedEvent) {}
+
+    fn handle_topology_description_changed_event(&self, event: TopologyDescriptionChangedEvent) {
+        let mut handle = DATABASE_TOPOLOGY.lock().unwrap();
+        handle.servers = event
+            .new_description
+            .servers()
+            .into_iter()
+            .map(|(address, description)| ServerDescription {
+                address: address.to_string(),
+                server_type: format!("{:?}", description.server_type()),
+            })
+            .collect();
+    }
+}
+
+#[cfg(test)]
+mod server_metric_tests {
+    use super::*;
+
+    fn bucket_line<'a>(out: &'a str, command: &str, le: &str) -> &'a str {
+        out.lines()
+            .find(|line| {
+                line.starts_with("mongodb_command_duration_milliseconds_bucket")
+                    && line.contains(&format!("command=\"{command}\""))
+                    && line.contains(&format!("le=\"{le}\""))
+            })
+            .unwrap_or_else(|| panic!("no bucket line for command={command} le={le} in:\n{out}"))
+    }
+
+    fn bucket_count(out: &str, command: &str, le: &str) -> u64 {
+        bucket_line(out, command, le)
+            .rsplit(' ')
+            .next()
+            .unwrap()
+            .parse()
+            .unwrap()
+    }
+
+    #[test]
+    fn render_prometheus_emits_non_decreasing_cumulative_bucket_counts() {
+        let mut metric = ServerMetric::default();
+        for millis in [2u64, 6, 12] {
+            metric.record(FinishedCommandInfo {
+                command_name: "find".to_string(),
+                duration: Duration::from_millis(millis),
+                succeeded: true,
+            });
+        }
+
+        let mut out = String::new();
+        metric.render_prometheus(&mut out);
+
+        let expected = [
+            ("1", 0),
+            ("5", 1),
+            ("10", 2),
+            ("25", 3),
+            ("50", 3),
+            ("100", 3),
+            ("250", 3),
+            ("500", 3),
+            ("1000", 3),
+        ];
+        for (le, count) in expected {
+            assert_eq!(bucket_count(&out, "find", le), count, "le={le}");
+        }
+        assert_eq!(bucket_count(&out, "find", "+Inf"), 3);
+        assert!(out.contains("mongodb_command_duration_milliseconds_sum{command=\"find\"} 20"));
+        assert!(out.contains("mongodb_command_duration_milliseconds_count{command=\"find\"} 3"));
+    }
+
+    #[test]
+    fn render_prometheus_keeps_commands_in_separate_series() {
+        let mut metric = ServerMetric::default();
+        metric.record(FinishedCommandInfo {
+            command_name: "find".to_string(),
+            duration: Duration::from_millis(2),
+            succeeded: true,
+        });
+        metric.record(FinishedCommandInfo {
+            command_name: "insert".to_string(),
+            duration: Duration::from_millis(30),
+            succeeded: true,
+        });
+
+        let mut out = String::new();
+        metric.render_prometheus(&mut out);
+
+        assert_eq!(bucket_count(&out, "find", "5"), 1);
+        assert_eq!(bucket_count(&out, "find", "25"), 1);
+        assert_eq!(bucket_count(&out, "insert", "5"), 0);
+        assert_eq!(bucket_count(&out, "insert", "50"), 1);
+    }
+}
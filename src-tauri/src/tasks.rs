@@ -0,0 +1,386 @@
+use std::collections::{HashMap, VecDeque};
+use std::sync::{Arc, Mutex};
+use std::time::Instant;
+
+use futures::stream::TryStreamExt;
+use mongodb::bson::Document;
+use mongodb::options::FindOptions;
+use mongodb::Client;
+use serde::Serialize;
+use tokio::sync::watch;
+use uuid::Uuid;
+
+use crate::error::PError;
+use crate::model::{BsonType, QueryRequest};
+
+/// How many finished tasks `TaskStore` keeps around after completion, so
+/// their results stay fetchable for a while without growing unbounded.
+const RECENT_TASK_CAPACITY: usize = 128;
+
+/// Progress every row in `rows_so_far` is batched by before the store is
+/// re-locked, to keep cursor draining from contending on every single row.
+const PROGRESS_BATCH_SIZE: u64 = 100;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TaskState {
+    Enqueued,
+    Processing,
+    Succeeded,
+    Failed,
+    Cancelled,
+}
+
+/// The payload a finished `mongodb_find_documents`/`mongodb_aggregate_documents`
+/// /`mongodb_analyze_documents` style query produced, tagged so the UI can
+/// tell which one it's looking at.
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "kind")]
+pub enum TaskQueryResult {
+    Documents {
+        documents: Vec<Document>,
+    },
+    FieldTypeCounts {
+        counts: Vec<(String, Vec<(BsonType, u64)>)>,
+    },
+}
+
+/// What the UI polls for via `mongodb_get_task`/`mongodb_list_tasks`.
+#[derive(Debug, Clone, Serialize)]
+pub struct TaskSnapshot {
+    pub task_id: Uuid,
+    pub state: TaskState,
+    pub rows_so_far: u64,
+    pub elapsed_ms: u128,
+    pub error: Option<String>,
+    pub result: Option<Arc<TaskQueryResult>>,
+}
+
+struct TaskEntry {
+    state: TaskState,
+    rows_so_far: u64,
+    started_at: Instant,
+    error: Option<String>,
+    result: Option<Arc<TaskQueryResult>>,
+    cancel: watch::Sender<bool>,
+}
+
+impl TaskEntry {
+    fn snapshot(&self, task_id: Uuid) -> TaskSnapshot {
+        TaskSnapshot {
+            task_id,
+            state: self.state,
+            rows_so_far: self.rows_so_far,
+            elapsed_ms: self.started_at.elapsed().as_millis(),
+            error: self.error.clone(),
+            result: self.result.clone(),
+        }
+    }
+}
+
+/// Registry of in-flight and recently-completed background queries,
+/// mirroring an asynchronous task-queue: `Enqueued` -> `Processing` ->
+/// `Succeeded`/`Failed`/`Cancelled`.
+#[derive(Default)]
+pub struct TaskStore {
+    tasks: HashMap<Uuid, TaskEntry>,
+    recent: VecDeque<Uuid>,
+}
+
+impl TaskStore {
+    fn evict_if_needed(&mut self, task_id: Uuid) {
+        self.recent.push_back(task_id);
+        if self.recent.len() > RECENT_TASK_CAPACITY {
+            if let Some(oldest) = self.recent.pop_front() {
+                self.tasks.remove(&oldest);
+            }
+        }
+    }
+
+    pub fn get(&self, task_id: Uuid) -> Option<TaskSnapshot> {
+        self.tasks.get(&task_id).map(|entry| entry.snapshot(task_id))
+    }
+
+    pub fn list(&self) -> Vec<TaskSnapshot> {
+        self.tasks
+            .iter()
+            .map(|(task_id, entry)| entry.snapshot(*task_id))
+            .collect()
+    }
+
+    /// Requests cancellation of a still-running task. Returns `false` if
+    /// the task is unknown or already finished.
+    pub fn cancel(&self, task_id: Uuid) -> bool {
+        match self.tasks.get(&task_id) {
+            Some(entry) if matches!(entry.state, TaskState::Enqueued | TaskState::Processing) => {
+                let _ = entry.cancel.send(true);
+                true
+            }
+            _ => false,
+        }
+    }
+}
+
+lazy_static! {
+    pub static ref TASK_STORE: Mutex<TaskStore> = Mutex::new(TaskStore::default());
+}
+
+/// Enqueues `request` on a tokio worker and returns its task id
+/// immediately; the worker reports progress and the final result back
+/// through `TASK_STORE` as it runs.
+pub fn submit(client: Client, request: QueryRequest) -> Uuid {
+    let task_id = Uuid::new_v4();
+    let (cancel_tx, cancel_rx) = watch::channel(false);
+
+    {
+        let mut store = TASK_STORE.lock().unwrap();
+        store.tasks.insert(
+            task_id,
+            TaskEntry {
+                state: TaskState::Enqueued,
+                rows_so_far: 0,
+                started_at: Instant::now(),
+                error: None,
+                result: None,
+                cancel: cancel_tx,
+            },
+        );
+    }
+
+    tokio::spawn(run_task(task_id, client, request, cancel_rx));
+
+    task_id
+}
+
+async fn run_task(
+    task_id: Uuid,
+    client: Client,
+    request: QueryRequest,
+    mut cancel_rx: watch::Receiver<bool>,
+) {
+    if let Some(entry) = TASK_STORE.lock().unwrap().tasks.get_mut(&task_id) {
+        entry.state = TaskState::Processing;
+    }
+
+    let outcome = run_query(task_id, client, request, &mut cancel_rx).await;
+
+    let mut store = TASK_STORE.lock().unwrap();
+    if let Some(entry) = store.tasks.get_mut(&task_id) {
+        match outcome {
+            Ok(Some(result)) => {
+                entry.state = TaskState::Succeeded;
+                entry.result = Some(Arc::new(result));
+            }
+            Ok(None) => entry.state = TaskState::Cancelled,
+            Err(err) => {
+                entry.state = TaskState::Failed;
+                entry.error = Some(err.to_string());
+            }
+        }
+    }
+    store.evict_if_needed(task_id);
+}
+
+/// Updates `rows_so_far`. Batched updates (`force: false`) skip the lock
+/// unless `rows_so_far` lands on a `PROGRESS_BATCH_SIZE` boundary, to keep
+/// cursor draining from contending on every single row; `force: true`
+/// always writes through, for the final count on every exit path
+/// (success, cancellation, or error) so a finished task never reports a
+/// stale, pre-rounding row count.
+fn report_progress(task_id: Uuid, rows_so_far: u64, force: bool) {
+    if !force && rows_so_far % PROGRESS_BATCH_SIZE != 0 {
+        return;
+    }
+    if let Some(entry) = TASK_STORE.lock().unwrap().tasks.get_mut(&task_id) {
+        entry.rows_so_far = rows_so_far;
+    }
+}
+
+/// Races `future` against `cancel_rx`, so cancellation takes effect even
+/// while `future` is a driver call that hasn't produced a cursor yet (e.g.
+/// the initial `find`/`aggregate` that's still doing query planning on a
+/// slow server), not just while rows are being streamed afterward.
+async fn race_cancellation<T, Fut>(
+    cancel_rx: &mut watch::Receiver<bool>,
+    future: Fut,
+) -> Result<Option<T>, PError>
+where
+    Fut: std::future::Future<Output = Result<T, mongodb::error::Error>>,
+{
+    tokio::pin!(future);
+    loop {
+        tokio::select! {
+            biased;
+            changed = cancel_rx.changed() => {
+                if changed.is_err() || *cancel_rx.borrow() {
+                    return Ok(None);
+                }
+            }
+            result = &mut future => {
+                return Ok(Some(result?));
+            }
+        }
+    }
+}
+
+/// Drains the cursor for `request`, bailing out early with `Ok(None)` if
+/// cancellation is requested through `cancel_rx` at any point, including
+/// while the initial `find`/`aggregate` call is still outstanding.
+async fn run_query(
+    task_id: Uuid,
+    client: Client,
+    request: QueryRequest,
+    cancel_rx: &mut watch::Receiver<bool>,
+) -> Result<Option<TaskQueryResult>, PError> {
+    match request {
+        QueryRequest::Find {
+            database_name,
+            collection_name,
+            documents_filter,
+            documents_projection,
+            documents_sort,
+        } => {
+            let collection = client
+                .database(&database_name)
+                .collection::<Document>(&collection_name);
+            let options = FindOptions::builder()
+                .projection(documents_projection)
+                .sort(documents_sort)
+                .build();
+            let mut cursor =
+                match race_cancellation(cancel_rx, collection.find(documents_filter, options))
+                    .await?
+                {
+                    Some(cursor) => cursor,
+                    None => return Ok(None),
+                };
+
+            let mut documents = Vec::new();
+            loop {
+                tokio::select! {
+                    biased;
+                    changed = cancel_rx.changed() => {
+                        if changed.is_err() || *cancel_rx.borrow() {
+                            report_progress(task_id, documents.len() as u64, true);
+                            return Ok(None);
+                        }
+                    }
+                    next = cursor.try_next() => {
+                        match next {
+                            Ok(Some(document)) => {
+                                documents.push(document);
+                                report_progress(task_id, documents.len() as u64, false);
+                            }
+                            Ok(None) => break,
+                            Err(err) => {
+                                report_progress(task_id, documents.len() as u64, true);
+                                return Err(err.into());
+                            }
+                        }
+                    }
+                }
+            }
+            report_progress(task_id, documents.len() as u64, true);
+            Ok(Some(TaskQueryResult::Documents { documents }))
+        }
+        QueryRequest::Aggregate {
+            database_name,
+            collection_name,
+            stages,
+        } => {
+            let collection = client
+                .database(&database_name)
+                .collection::<Document>(&collection_name);
+            let mut cursor =
+                match race_cancellation(cancel_rx, collection.aggregate(stages, None)).await? {
+                    Some(cursor) => cursor,
+                    None => return Ok(None),
+                };
+
+            let mut documents = Vec::new();
+            loop {
+                tokio::select! {
+                    biased;
+                    changed = cancel_rx.changed() => {
+                        if changed.is_err() || *cancel_rx.borrow() {
+                            report_progress(task_id, documents.len() as u64, true);
+                            return Ok(None);
+                        }
+                    }
+                    next = cursor.try_next() => {
+                        match next {
+                            Ok(Some(document)) => {
+                                documents.push(document);
+                                report_progress(task_id, documents.len() as u64, false);
+                            }
+                            Ok(None) => break,
+                            Err(err) => {
+                                report_progress(task_id, documents.len() as u64, true);
+                                return Err(err.into());
+                            }
+                        }
+                    }
+                }
+            }
+            report_progress(task_id, documents.len() as u64, true);
+            Ok(Some(TaskQueryResult::Documents { documents }))
+        }
+        QueryRequest::Analyze {
+            database_name,
+            collection_name,
+            documents_filter,
+        } => {
+            let collection = client
+                .database(&database_name)
+                .collection::<Document>(&collection_name);
+            let options = FindOptions::builder().limit(1000).build();
+            let mut cursor =
+                match race_cancellation(cancel_rx, collection.find(documents_filter, options))
+                    .await?
+                {
+                    Some(cursor) => cursor,
+                    None => return Ok(None),
+                };
+
+            let mut counts: HashMap<String, HashMap<BsonType, u64>> = HashMap::default();
+            let mut rows_seen = 0u64;
+            loop {
+                tokio::select! {
+                    biased;
+                    changed = cancel_rx.changed() => {
+                        if changed.is_err() || *cancel_rx.borrow() {
+                            report_progress(task_id, rows_seen, true);
+                            return Ok(None);
+                        }
+                    }
+                    next = cursor.try_next() => {
+                        match next {
+                            Ok(Some(document)) => {
+                                for (key, value) in &document {
+                                    *counts
+                                        .entry(key.to_string())
+                                        .or_default()
+                                        .entry(BsonType::from(value))
+                                        .or_default() += 1;
+                                }
+                                rows_seen += 1;
+                                report_progress(task_id, rows_seen, false);
+                            }
+                            Ok(None) => break,
+                            Err(err) => {
+                                report_progress(task_id, rows_seen, true);
+                                return Err(err.into());
+                            }
+                        }
+                    }
+                }
+            }
+            report_progress(task_id, rows_seen, true);
+            let counts = counts
+                .into_iter()
+                .map(|(key, value)| (key, value.into_iter().collect()))
+                .collect();
+            Ok(Some(TaskQueryResult::FieldTypeCounts { counts }))
+        }
+    }
+}